@@ -0,0 +1,262 @@
+/// Inbound control plane for connected Neovim instances. Pairs with the
+/// broadcast loop in `main`: where that loop pushes `HitEvent` JSON out to
+/// every client, this module reads newline-delimited JSON commands back in
+/// and applies them to a shared `DetectorConfig`, so sensitivity/cooldown/
+/// undo-count changes and pause take effect immediately without restarting
+/// `sudo smack`.
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::UnixStream;
+use std::sync::{Arc, Mutex};
+
+use crate::detector::{Sensitivity, Severity};
+
+/// Per-severity undo counts, overridable independently via `set_undos`.
+#[derive(Debug, Clone, Copy)]
+pub struct UndosConfig {
+    pub light: u32,
+    pub medium: u32,
+    pub hard: u32,
+}
+
+impl Default for UndosConfig {
+    fn default() -> Self {
+        Self {
+            light: 1,
+            medium: 3,
+            hard: 5,
+        }
+    }
+}
+
+impl UndosConfig {
+    pub fn undos_for(&self, severity: Severity) -> u32 {
+        match severity {
+            Severity::Light => self.light,
+            Severity::Medium => self.medium,
+            Severity::Hard => self.hard,
+        }
+    }
+}
+
+/// Live-tunable detector settings, shared between the detection loop and
+/// every connected client's command reader via `Arc<Mutex<_>>`.
+#[derive(Debug, Clone, Copy)]
+pub struct DetectorConfig {
+    pub sensitivity: Sensitivity,
+    pub cooldown_ms: u32,
+    pub undos: UndosConfig,
+    pub paused: bool,
+}
+
+impl Default for DetectorConfig {
+    fn default() -> Self {
+        Self {
+            sensitivity: Sensitivity::default(),
+            cooldown_ms: 500,
+            undos: UndosConfig::default(),
+            paused: false,
+        }
+    }
+}
+
+/// A parsed inbound command. See `parse_command` for the wire format.
+enum Command {
+    SetSensitivity {
+        light: f64,
+        medium: f64,
+        hard: f64,
+    },
+    SetCooldownMs(u32),
+    SetUndos {
+        light: Option<u32>,
+        medium: Option<u32>,
+        hard: Option<u32>,
+    },
+    Pause,
+    Resume,
+}
+
+fn apply_command(config: &mut DetectorConfig, cmd: Command) {
+    match cmd {
+        Command::SetSensitivity {
+            light,
+            medium,
+            hard,
+        } => {
+            config.sensitivity.k_light = light;
+            config.sensitivity.k_medium = medium;
+            config.sensitivity.k_hard = hard;
+        }
+        Command::SetCooldownMs(ms) => config.cooldown_ms = ms,
+        Command::SetUndos {
+            light,
+            medium,
+            hard,
+        } => {
+            if let Some(v) = light {
+                config.undos.light = v;
+            }
+            if let Some(v) = medium {
+                config.undos.medium = v;
+            }
+            if let Some(v) = hard {
+                config.undos.hard = v;
+            }
+        }
+        Command::Pause => config.paused = true,
+        Command::Resume => config.paused = false,
+    }
+}
+
+// ── Minimal flat-JSON parsing ───────────────────────────────────────────────
+//
+// Commands are always a single flat object of string/number fields —
+// no nesting, no arrays — so a tiny hand-rolled parser is enough and keeps
+// this daemon free of a JSON-crate dependency.
+
+#[derive(Debug, Clone)]
+enum JsonValue {
+    Str(String),
+    Num(f64),
+}
+
+fn split_top_level(s: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut in_str = false;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '"' => in_str = !in_str,
+            ',' if !in_str => {
+                parts.push(&s[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    let tail = s[start..].trim();
+    if !tail.is_empty() {
+        parts.push(&s[start..]);
+    }
+    parts
+}
+
+fn parse_json_string(s: &str) -> Result<String, String> {
+    let s = s.trim();
+    s.strip_prefix('"')
+        .and_then(|s| s.strip_suffix('"'))
+        .map(str::to_string)
+        .ok_or_else(|| format!("expected a JSON string, got: {s}"))
+}
+
+fn parse_json_value(s: &str) -> Result<JsonValue, String> {
+    let s = s.trim();
+    if s.starts_with('"') {
+        return parse_json_string(s).map(JsonValue::Str);
+    }
+    s.parse::<f64>()
+        .map(JsonValue::Num)
+        .map_err(|_| format!("unrecognized JSON value: {s}"))
+}
+
+/// Parse a single flat JSON object into key/value pairs.
+fn parse_flat_object(line: &str) -> Result<Vec<(String, JsonValue)>, String> {
+    let body = line
+        .trim()
+        .strip_prefix('{')
+        .and_then(|s| s.strip_suffix('}'))
+        .ok_or_else(|| "expected a JSON object".to_string())?;
+
+    let mut pairs = Vec::new();
+    for entry in split_top_level(body) {
+        let entry = entry.trim();
+        if entry.is_empty() {
+            continue;
+        }
+        let (key, value) = entry
+            .split_once(':')
+            .ok_or_else(|| format!("malformed entry: {entry}"))?;
+        pairs.push((parse_json_string(key)?, parse_json_value(value)?));
+    }
+    Ok(pairs)
+}
+
+fn find<'a>(pairs: &'a [(String, JsonValue)], key: &str) -> Option<&'a JsonValue> {
+    pairs.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+}
+
+fn find_num(pairs: &[(String, JsonValue)], key: &str) -> Option<f64> {
+    match find(pairs, key) {
+        Some(JsonValue::Num(n)) => Some(*n),
+        _ => None,
+    }
+}
+
+/// Parse one newline-delimited command. Supported shapes:
+///   {"cmd":"set_sensitivity","light":6.0,"medium":12.0,"hard":20.0}
+///   {"cmd":"set_cooldown_ms","ms":500}
+///   {"cmd":"set_undos","hard":5}
+///   {"cmd":"pause"} / {"cmd":"resume"}
+fn parse_command(line: &str) -> Result<Command, String> {
+    let pairs = parse_flat_object(line)?;
+    let cmd = match find(&pairs, "cmd") {
+        Some(JsonValue::Str(s)) => s.as_str(),
+        _ => return Err("missing \"cmd\" field".into()),
+    };
+
+    match cmd {
+        "set_sensitivity" => Ok(Command::SetSensitivity {
+            light: find_num(&pairs, "light").ok_or("set_sensitivity requires \"light\"")?,
+            medium: find_num(&pairs, "medium").ok_or("set_sensitivity requires \"medium\"")?,
+            hard: find_num(&pairs, "hard").ok_or("set_sensitivity requires \"hard\"")?,
+        }),
+        "set_cooldown_ms" => {
+            let ms = find_num(&pairs, "ms").ok_or("set_cooldown_ms requires \"ms\"")?;
+            Ok(Command::SetCooldownMs(ms as u32))
+        }
+        "set_undos" => Ok(Command::SetUndos {
+            light: find_num(&pairs, "light").map(|n| n as u32),
+            medium: find_num(&pairs, "medium").map(|n| n as u32),
+            hard: find_num(&pairs, "hard").map(|n| n as u32),
+        }),
+        "pause" => Ok(Command::Pause),
+        "resume" => Ok(Command::Resume),
+        other => Err(format!("unknown command: {other}")),
+    }
+}
+
+/// Read newline-delimited JSON commands from a client and apply them to the
+/// shared config, acking each one back over the same connection. Runs until
+/// the client disconnects.
+pub fn handle_client(stream: UnixStream, config: Arc<Mutex<DetectorConfig>>) {
+    let mut writer = match stream.try_clone() {
+        Ok(w) => w,
+        Err(e) => {
+            eprintln!("smack: failed to open command channel for client: {e}");
+            return;
+        }
+    };
+
+    for line in BufReader::new(stream).lines() {
+        let line = match line {
+            Ok(l) => l,
+            Err(_) => break,
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let ack = match parse_command(&line) {
+            Ok(cmd) => {
+                apply_command(&mut config.lock().unwrap(), cmd);
+                r#"{"ok":true}"#.to_string()
+            }
+            Err(e) => format!(r#"{{"ok":false,"error":"{}"}}"#, e.replace('"', "'")),
+        };
+
+        if writeln!(writer, "{ack}").is_err() {
+            break;
+        }
+        writer.flush().ok();
+    }
+}
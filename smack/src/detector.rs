@@ -1,5 +1,10 @@
-/// Simple threshold-based impact detector.
-/// Classifies hits into three tiers based on acceleration magnitude.
+/// Adaptive, noise-tracking impact detector.
+/// Classifies hits into three tiers based on how far the acceleration
+/// magnitude exceeds the signal's own recent noise floor, rather than
+/// fixed g-thresholds — so the same sensitivity feels right on a desk,
+/// a couch, or a moving train.
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
 
 #[derive(Debug, Clone, Copy)]
 pub enum Severity {
@@ -16,12 +21,77 @@ impl Severity {
             Severity::Hard => "hard",
         }
     }
+}
+
+/// Which side/corner of the laptop took the hit, relative to the
+/// gravity-baseline vector at rest. Lets Neovim bind different actions to
+/// different smack directions (undo on a left smack, redo on a right
+/// smack, `:w` on a top smack).
+#[derive(Debug, Clone, Copy)]
+pub enum Direction {
+    Top,    // lid
+    Bottom, // palmrest/desk side
+    Left,
+    Right,
+    Front,
+    Back,
+}
+
+impl Direction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Direction::Top => "top",
+            Direction::Bottom => "bottom",
+            Direction::Left => "left",
+            Direction::Right => "right",
+            Direction::Front => "front",
+            Direction::Back => "back",
+        }
+    }
+
+    /// Classify the dominant-magnitude axis of a delta vector (impact
+    /// reading minus gravity baseline) into a direction.
+    fn from_delta(dx: f64, dy: f64, dz: f64) -> Self {
+        let adx = dx.abs();
+        let ady = dy.abs();
+        let adz = dz.abs();
+
+        if adx >= ady && adx >= adz {
+            if dx >= 0.0 {
+                Direction::Right
+            } else {
+                Direction::Left
+            }
+        } else if ady >= adx && ady >= adz {
+            if dy >= 0.0 {
+                Direction::Back
+            } else {
+                Direction::Front
+            }
+        } else if dz >= 0.0 {
+            Direction::Top
+        } else {
+            Direction::Bottom
+        }
+    }
+}
 
-    pub fn undos(&self) -> u32 {
+/// A deliberate multi-sample motion, layered on top of single-impact
+/// severity so Neovim can bind heavier actions (discard buffer, close
+/// window) to gestures while a single tap keeps mapping to undos.
+#[derive(Debug, Clone, Copy)]
+pub enum Gesture {
+    /// Angular velocity reversed direction repeatedly in a short window.
+    Shake,
+    /// Two impacts landed in quick succession.
+    DoubleSmack,
+}
+
+impl Gesture {
+    pub fn as_str(&self) -> &'static str {
         match self {
-            Severity::Light => 1,
-            Severity::Medium => 3,
-            Severity::Hard => 5,
+            Gesture::Shake => "shake",
+            Gesture::DoubleSmack => "double_smack",
         }
     }
 }
@@ -29,72 +99,260 @@ impl Severity {
 pub struct HitEvent {
     pub severity: Severity,
     pub amplitude: f64,
+    pub direction: Direction,
+    pub gesture: Option<Gesture>,
+}
+
+// Gyro reading (deg/s) past which a sample counts as "moving" for shake
+// purposes — well above ordinary handling jitter.
+const SHAKE_THRESHOLD_DPS: f64 = 150.0;
+// Alternating-sign crossings of the dominant gyro axis required inside
+// `SHAKE_WINDOW` to call it a shake rather than a single twist.
+const SHAKE_MIN_CROSSINGS: usize = 4;
+const SHAKE_WINDOW: Duration = Duration::from_millis(600);
+// Two impacts landing within this long of each other count as one
+// deliberate double-smack gesture.
+const DOUBLE_SMACK_WINDOW: Duration = Duration::from_millis(400);
+
+// Samples arrive at roughly this rate from every backend (the BMI286 HID
+// path decimates to it, the SMS backend polls to match) — used to convert
+// a cooldown duration in milliseconds to a sample count.
+const SAMPLE_RATE_HZ: f64 = 100.0;
+
+/// Sensitivity knobs for `Detector`, expressed as multipliers of the
+/// signal's running noise deviation plus an absolute floor. Higher k means
+/// a harder smack is needed to reach that tier.
+#[derive(Debug, Clone, Copy)]
+pub struct Sensitivity {
+    pub k_light: f64,
+    pub k_medium: f64,
+    pub k_hard: f64,
+    /// Minimum excess-over-baseline (in g) required to trigger at all,
+    /// regardless of how small the tracked noise deviation is. Stops a
+    /// perfectly still sensor from firing on rounding noise.
+    pub floor: f64,
+}
+
+impl Default for Sensitivity {
+    fn default() -> Self {
+        Self {
+            k_light: 6.0,
+            k_medium: 12.0,
+            k_hard: 20.0,
+            floor: 0.2,
+        }
+    }
 }
 
 pub struct Detector {
     baseline: f64,
+    // Gravity-baseline vector, tracked with the same EMA as `baseline` so
+    // impacts can be classified by which axis they displaced.
+    bx: f64,
+    by: f64,
+    bz: f64,
+    // Running mean-absolute-deviation of the magnitude signal around
+    // `baseline` — the detector's notion of "how noisy is this surface".
+    dev: f64,
     samples_seen: u64,
+    // Configured cooldown length, and how many samples of it remain.
+    cooldown_samples: u32,
     cooldown_remaining: u32,
+    sensitivity: Sensitivity,
+    // Timestamps of recent alternating-sign gyro crossings, pruned to
+    // `SHAKE_WINDOW`, plus the sign of the last crossing seen.
+    shake_crossings: VecDeque<Instant>,
+    last_shake_sign: i8,
+    last_hit_at: Option<Instant>,
 }
 
 impl Detector {
     pub fn new() -> Self {
+        Self::with_sensitivity(Sensitivity::default())
+    }
+
+    pub fn with_sensitivity(sensitivity: Sensitivity) -> Self {
         Self {
             baseline: 1.0, // ~1g at rest (gravity)
+            bx: 0.0,
+            by: 0.0,
+            bz: 1.0, // assume lid-up resting orientation until calibration settles
+            dev: 0.0,
             samples_seen: 0,
+            cooldown_samples: 50, // ~500ms at SAMPLE_RATE_HZ
             cooldown_remaining: 0,
+            sensitivity,
+            shake_crossings: VecDeque::new(),
+            last_shake_sign: 0,
+            last_hit_at: None,
+        }
+    }
+
+    /// Feed a gyro reading into the shake tracker and report whether a
+    /// shake gesture is newly complete. Runs independently of the
+    /// accelerometer calibration/cooldown state — rotation is tracked on
+    /// every sample, impact or not.
+    fn note_gyro(&mut self, gx: f64, gy: f64, gz: f64, now: Instant) -> bool {
+        let mut dominant = gx;
+        if gy.abs() > dominant.abs() {
+            dominant = gy;
+        }
+        if gz.abs() > dominant.abs() {
+            dominant = gz;
+        }
+
+        if dominant.abs() >= SHAKE_THRESHOLD_DPS {
+            let sign: i8 = if dominant >= 0.0 { 1 } else { -1 };
+            if self.last_shake_sign != 0 && sign != self.last_shake_sign {
+                self.shake_crossings.push_back(now);
+            }
+            self.last_shake_sign = sign;
+        }
+
+        while let Some(&oldest) = self.shake_crossings.front() {
+            if now.duration_since(oldest) > SHAKE_WINDOW {
+                self.shake_crossings.pop_front();
+            } else {
+                break;
+            }
+        }
+
+        if self.shake_crossings.len() >= SHAKE_MIN_CROSSINGS {
+            self.shake_crossings.clear();
+            self.last_shake_sign = 0;
+            true
+        } else {
+            false
         }
     }
 
-    /// Process a single accelerometer sample (x, y, z in g-force).
-    /// Returns a HitEvent if an impact is detected.
-    pub fn process(&mut self, x: f64, y: f64, z: f64) -> Option<HitEvent> {
+    /// Build the HitEvent for a shake gesture with no accompanying linear
+    /// impact, using the current gravity baseline for direction/amplitude.
+    fn shake_hit(&self, mag: f64, x: f64, y: f64, z: f64) -> HitEvent {
+        HitEvent {
+            severity: Severity::Light,
+            amplitude: mag - self.baseline,
+            direction: Direction::from_delta(x - self.bx, y - self.by, z - self.bz),
+            gesture: Some(Gesture::Shake),
+        }
+    }
+
+    /// Replace the sensitivity multipliers/floor in place, taking effect on
+    /// the next sample. Lets a connected client retune live.
+    pub fn set_sensitivity(&mut self, sensitivity: Sensitivity) {
+        self.sensitivity = sensitivity;
+    }
+
+    /// Reconfigure the post-hit cooldown window. Takes effect the next time
+    /// a hit fires; a cooldown already in progress runs to its old length.
+    pub fn set_cooldown_ms(&mut self, ms: u32) {
+        self.cooldown_samples = ((ms as f64) * SAMPLE_RATE_HZ / 1000.0).round() as u32;
+    }
+
+    /// Process a single IMU sample: x/y/z in g-force, gx/gy/gz in deg/s.
+    /// Returns a HitEvent if an impact and/or gesture is detected.
+    pub fn process(
+        &mut self,
+        x: f64,
+        y: f64,
+        z: f64,
+        gx: f64,
+        gy: f64,
+        gz: f64,
+    ) -> Option<HitEvent> {
+        let now = Instant::now();
+        let shook = self.note_gyro(gx, gy, gz, now);
+
         let mag = (x * x + y * y + z * z).sqrt();
 
-        // Calibration period: let the baseline settle
+        // Calibration period: let the baseline and noise floor settle
         if self.samples_seen < 100 {
+            self.dev = self.dev * 0.9 + (mag - self.baseline).abs() * 0.1;
             self.baseline = self.baseline * 0.9 + mag * 0.1;
+            self.bx = self.bx * 0.9 + x * 0.1;
+            self.by = self.by * 0.9 + y * 0.1;
+            self.bz = self.bz * 0.9 + z * 0.1;
             self.samples_seen += 1;
-            return None;
+            // Shake detection runs independently of calibration, so a
+            // shake completing mid-calibration still needs to be surfaced.
+            return if shook {
+                Some(self.shake_hit(mag, x, y, z))
+            } else {
+                None
+            };
         }
         self.samples_seen += 1;
 
-        // Cooldown: ignore samples after a recent hit to avoid multi-triggering
+        // Cooldown: ignore samples after a recent hit to avoid
+        // multi-triggering, and freeze the baseline/noise estimate so the
+        // impact itself doesn't get folded into "normal" noise.
         if self.cooldown_remaining > 0 {
             self.cooldown_remaining -= 1;
-            // Still update baseline slowly during cooldown
-            self.baseline = self.baseline * 0.999 + mag * 0.001;
-            return None;
+            // Likewise, a shake completing during cooldown is still a
+            // deliberate gesture and must not be silently eaten.
+            return if shook {
+                Some(self.shake_hit(mag, x, y, z))
+            } else {
+                None
+            };
         }
 
         let excess = mag - self.baseline;
+        let direction = Direction::from_delta(x - self.bx, y - self.by, z - self.bz);
 
-        // Update baseline slowly (tracks drift but not impacts)
+        // Update baseline and noise deviation slowly (tracks drift but not impacts)
+        self.dev = self.dev * 0.99 + excess.abs() * 0.01;
         self.baseline = self.baseline * 0.999 + mag * 0.001;
+        self.bx = self.bx * 0.999 + x * 0.001;
+        self.by = self.by * 0.999 + y * 0.001;
+        self.bz = self.bz * 0.999 + z * 0.001;
+
+        // Thresholds scale with the tracked noise deviation, floored so a
+        // perfectly still sensor can't trigger on rounding noise.
+        let light_threshold = (self.sensitivity.k_light * self.dev).max(self.sensitivity.floor);
+        let medium_threshold = (self.sensitivity.k_medium * self.dev).max(self.sensitivity.floor);
+        let hard_threshold = (self.sensitivity.k_hard * self.dev).max(self.sensitivity.floor);
 
-        // Thresholds (in g above baseline)
-        let result = if excess > 2.0 {
+        let mut result = if excess > hard_threshold {
             Some(HitEvent {
                 severity: Severity::Hard,
                 amplitude: excess,
+                direction,
+                gesture: None,
             })
-        } else if excess > 1.0 {
+        } else if excess > medium_threshold {
             Some(HitEvent {
                 severity: Severity::Medium,
                 amplitude: excess,
+                direction,
+                gesture: None,
             })
-        } else if excess > 0.3 {
+        } else if excess > light_threshold {
             Some(HitEvent {
                 severity: Severity::Light,
                 amplitude: excess,
+                direction,
+                gesture: None,
             })
         } else {
             None
         };
 
-        if result.is_some() {
-            // ~500ms cooldown at ~100Hz sample rate
-            self.cooldown_remaining = 50;
+        if let Some(event) = result.as_mut() {
+            self.cooldown_remaining = self.cooldown_samples;
+
+            // Two impacts close enough together are one deliberate
+            // double-smack gesture rather than two separate taps.
+            if let Some(prev) = self.last_hit_at {
+                if now.duration_since(prev) <= DOUBLE_SMACK_WINDOW {
+                    event.gesture = Some(Gesture::DoubleSmack);
+                }
+            }
+            self.last_hit_at = Some(now);
+        } else if shook {
+            // No linear impact this sample, but the gyro ring buffer saw a
+            // deliberate shake — surface it as its own event.
+            result = Some(self.shake_hit(mag, x, y, z));
         }
 
         result
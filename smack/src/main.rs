@@ -1,10 +1,13 @@
+mod control;
 mod detector;
 mod sensor;
 
+use control::DetectorConfig;
+
 use std::io::Write;
 use std::os::unix::net::{UnixListener, UnixStream};
-use std::sync::{Arc, Mutex};
 use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
 use std::thread;
 
 const SOCKET_PATH: &str = "/tmp/smack.sock";
@@ -38,14 +41,27 @@ fn main() {
     listener.set_nonblocking(true).ok();
 
     let clients: Arc<Mutex<Vec<UnixStream>>> = Arc::new(Mutex::new(Vec::new()));
+    let config: Arc<Mutex<DetectorConfig>> = Arc::new(Mutex::new(DetectorConfig::default()));
 
     // Accept connections in background
     let clients_accept = clients.clone();
+    let config_accept = config.clone();
     thread::spawn(move || loop {
         match listener.accept() {
             Ok((stream, _)) => {
                 eprintln!("smack: client connected");
                 stream.set_nonblocking(false).ok();
+
+                // Inbound half: read commands back from this client and
+                // apply them to the shared config.
+                match stream.try_clone() {
+                    Ok(cmd_stream) => {
+                        let config = config_accept.clone();
+                        thread::spawn(move || control::handle_client(cmd_stream, config));
+                    }
+                    Err(e) => eprintln!("smack: failed to open command channel for client: {e}"),
+                }
+
                 clients_accept.lock().unwrap().push(stream);
             }
             Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => {
@@ -78,39 +94,65 @@ fn main() {
     let mut hit_count: u64 = 0;
 
     while let Ok(sample) = rx.recv() {
-        if let Some(event) = det.process(sample.x, sample.y, sample.z) {
+        // Pick up any live reconfiguration from a connected Neovim before
+        // processing this sample.
+        let (undos, paused) = {
+            let cfg = config.lock().unwrap();
+            det.set_sensitivity(cfg.sensitivity);
+            det.set_cooldown_ms(cfg.cooldown_ms);
+            (cfg.undos, cfg.paused)
+        };
+
+        if paused {
+            continue;
+        }
+
+        if let Some(event) = det.process(
+            sample.x, sample.y, sample.z, sample.gx, sample.gy, sample.gz,
+        ) {
             hit_count += 1;
+            let undo_count = undos.undos_for(event.severity);
+            // `direction` is always a real value; `gesture` is optional, so
+            // it's serialized as `null` rather than a sentinel empty string
+            // that a consumer could mistake for a valid gesture name.
+            let gesture_json = match event.gesture {
+                Some(g) => format!(r#""{}""#, g.as_str()),
+                None => "null".to_string(),
+            };
+            let gesture_log = event.gesture.map(|g| g.as_str()).unwrap_or("none");
 
             let json = format!(
-                r#"{{"severity":"{}","amplitude":{:.4},"undos":{}}}"#,
+                r#"{{"severity":"{}","amplitude":{:.4},"undos":{},"direction":"{}","gesture":{}}}"#,
                 event.severity.as_str(),
                 event.amplitude,
-                event.severity.undos(),
+                undo_count,
+                event.direction.as_str(),
+                gesture_json,
             );
 
             // Print to stdout
             eprintln!(
-                "smack: hit #{} [{}  amp={:.4}g  undos={}]",
+                "smack: hit #{} [{}  amp={:.4}g  undos={}  dir={}  gesture={}]",
                 hit_count,
                 event.severity.as_str(),
                 event.amplitude,
-                event.severity.undos(),
+                undo_count,
+                event.direction.as_str(),
+                gesture_log,
             );
             println!("{json}");
             std::io::stdout().flush().ok();
 
             // Broadcast to connected Neovim instances
             let mut clients = clients.lock().unwrap();
-            clients.retain_mut(|stream| {
-                match writeln!(stream, "{}", json) {
-                    Ok(_) => {
-                        stream.flush().ok();
-                        true
-                    }
-                    Err(_) => {
-                        eprintln!("smack: client disconnected");
-                        false
-                    }
+            clients.retain_mut(|stream| match writeln!(stream, "{}", json) {
+                Ok(_) => {
+                    stream.flush().ok();
+                    true
+                }
+                Err(_) => {
+                    eprintln!("smack: client disconnected");
+                    false
                 }
             });
         }
@@ -0,0 +1,592 @@
+/// Apple Silicon accelerometer reader via IOKit HID.
+/// Ported from https://github.com/olvvier/apple-silicon-accelerometer
+///
+/// Accesses the Bosch BMI286 IMU through the AppleSPU HID interface.
+/// Requires root privileges (sudo).
+use std::ffi::CString;
+use std::os::raw::c_void;
+use std::sync::mpsc;
+
+use super::{Sample, SensorSource};
+
+// ── Core Foundation type aliases ────────────────────────────────────────────
+
+type CFAllocatorRef = *const c_void;
+type CFStringRef = *const c_void;
+type CFNumberRef = *const c_void;
+type CFTypeRef = *const c_void;
+type CFDictionaryRef = *const c_void;
+type CFMutableDictionaryRef = *mut c_void;
+type CFRunLoopRef = *mut c_void;
+type CFRunLoopSourceRef = *mut c_void;
+type CFIndex = isize;
+
+// ── IOKit type aliases ──────────────────────────────────────────────────────
+
+type IOReturn = i32;
+type MachPort = u32;
+type IOIterator = u32;
+type IOObject = u32;
+type IOHIDDeviceRef = *mut c_void;
+type IONotificationPortRef = *mut c_void;
+
+// ── Constants ───────────────────────────────────────────────────────────────
+
+const KERN_SUCCESS: IOReturn = 0;
+const K_IO_MAIN_PORT_DEFAULT: MachPort = 0;
+const K_CF_ALLOCATOR_DEFAULT: CFAllocatorRef = std::ptr::null();
+const K_CF_STRING_ENCODING_UTF8: u32 = 0x08000100;
+const K_CF_NUMBER_SINT32_TYPE: CFIndex = 3;
+
+// HID usage identifiers for the accelerometer
+const PAGE_VENDOR: i32 = 0xFF00;
+const USAGE_ACCEL: i32 = 3;
+
+// HID report format (Bosch BMI286 IMU — a 6-axis part, so in principle the
+// same report can carry angular-velocity words right after the
+// accelerometer block). `IMU_REPORT_LEN` is the empirically-fixed report
+// size this sensor has always sent (see the ported reference
+// implementation) and must not be derived from assumed field offsets —
+// doing so would make the length guard below reject every real report and
+// silently kill the accelerometer. Gyro words are only decoded when a
+// report actually carries the extra bytes for them.
+const IMU_DATA_OFF: usize = 6;
+const ACCEL_SCALE: f64 = 65536.0; // Q16 fixed-point -> g
+const IMU_REPORT_LEN: usize = 22;
+const GYRO_DATA_OFF: usize = IMU_DATA_OFF + 12; // three i32 accel words
+const GYRO_SCALE: f64 = 16.4; // LSB per deg/s at the BMI286's default +-2000dps range
+const GYRO_REPORT_LEN: usize = GYRO_DATA_OFF + 6; // three i16 gyro words
+const IMU_DECIMATION: u32 = 8; // keep 1 in 8 samples (~800Hz -> ~100Hz)
+const REPORT_BUF_SZ: usize = 4096;
+const REPORT_INTERVAL_US: i32 = 1000;
+
+// Notification types from IOKitLib.h (io_name_t C strings, not CFStrings)
+const K_IO_FIRST_MATCH_NOTIFICATION: &str = "IOServiceFirstMatch";
+const K_IO_TERMINATED_NOTIFICATION: &str = "IOServiceTerminate";
+
+// ── FFI bindings ────────────────────────────────────────────────────────────
+
+#[link(name = "IOKit", kind = "framework")]
+extern "C" {
+    fn IOServiceMatching(name: *const i8) -> CFMutableDictionaryRef;
+    fn IOServiceGetMatchingServices(
+        mainPort: MachPort,
+        matching: CFDictionaryRef,
+        existing: *mut IOIterator,
+    ) -> IOReturn;
+    fn IOIteratorNext(iterator: IOIterator) -> IOObject;
+    fn IORegistryEntryCreateCFProperty(
+        entry: IOObject,
+        key: CFStringRef,
+        allocator: CFAllocatorRef,
+        options: u32,
+    ) -> CFTypeRef;
+    fn IORegistryEntrySetCFProperty(
+        entry: IOObject,
+        name: CFStringRef,
+        property: CFTypeRef,
+    ) -> IOReturn;
+    fn IOObjectRelease(object: IOObject) -> IOReturn;
+    fn IOHIDDeviceCreate(allocator: CFAllocatorRef, service: IOObject) -> IOHIDDeviceRef;
+    fn IOHIDDeviceOpen(device: IOHIDDeviceRef, options: u32) -> IOReturn;
+    fn IOHIDDeviceClose(device: IOHIDDeviceRef, options: u32) -> IOReturn;
+    fn IOHIDDeviceRegisterInputReportCallback(
+        device: IOHIDDeviceRef,
+        report: *mut u8,
+        reportLength: CFIndex,
+        callback: unsafe extern "C" fn(
+            context: *mut c_void,
+            result: IOReturn,
+            sender: *mut c_void,
+            report_type: u32,
+            report_id: u32,
+            report: *mut u8,
+            report_length: CFIndex,
+        ),
+        context: *mut c_void,
+    );
+    fn IOHIDDeviceScheduleWithRunLoop(
+        device: IOHIDDeviceRef,
+        runLoop: CFRunLoopRef,
+        runLoopMode: CFStringRef,
+    );
+    fn IONotificationPortCreate(mainPort: MachPort) -> IONotificationPortRef;
+    fn IONotificationPortGetRunLoopSource(notifyPort: IONotificationPortRef) -> CFRunLoopSourceRef;
+    fn IOServiceAddMatchingNotification(
+        notifyPort: IONotificationPortRef,
+        notificationType: *const i8,
+        matching: CFDictionaryRef,
+        callback: unsafe extern "C" fn(refcon: *mut c_void, iterator: IOIterator),
+        refCon: *mut c_void,
+        notification: *mut IOIterator,
+    ) -> IOReturn;
+}
+
+#[link(name = "CoreFoundation", kind = "framework")]
+extern "C" {
+    fn CFStringCreateWithCString(
+        alloc: CFAllocatorRef,
+        cStr: *const i8,
+        encoding: u32,
+    ) -> CFStringRef;
+    fn CFNumberCreate(
+        allocator: CFAllocatorRef,
+        theType: CFIndex,
+        valuePtr: *const c_void,
+    ) -> CFNumberRef;
+    fn CFNumberGetValue(number: CFNumberRef, theType: CFIndex, valuePtr: *mut c_void) -> bool;
+    fn CFRunLoopGetCurrent() -> CFRunLoopRef;
+    fn CFRunLoopAddSource(runLoop: CFRunLoopRef, source: CFRunLoopSourceRef, mode: CFStringRef);
+    fn CFRunLoopRunInMode(mode: CFStringRef, seconds: f64, returnAfterSourceHandled: bool) -> i32;
+    fn CFRelease(cf: CFTypeRef);
+
+    static kCFRunLoopDefaultMode: CFStringRef;
+}
+
+// ── Helpers ─────────────────────────────────────────────────────────────────
+
+fn cfstr(s: &str) -> CFStringRef {
+    let cstr = CString::new(s).unwrap();
+    unsafe {
+        CFStringCreateWithCString(
+            K_CF_ALLOCATOR_DEFAULT,
+            cstr.as_ptr(),
+            K_CF_STRING_ENCODING_UTF8,
+        )
+    }
+}
+
+fn cfnum32(val: i32) -> CFNumberRef {
+    unsafe {
+        CFNumberCreate(
+            K_CF_ALLOCATOR_DEFAULT,
+            K_CF_NUMBER_SINT32_TYPE,
+            &val as *const i32 as *const c_void,
+        )
+    }
+}
+
+fn prop_int(service: IOObject, key: &str) -> Option<i32> {
+    let cf_key = cfstr(key);
+    let cf_val =
+        unsafe { IORegistryEntryCreateCFProperty(service, cf_key, K_CF_ALLOCATOR_DEFAULT, 0) };
+    unsafe { CFRelease(cf_key) };
+
+    if cf_val.is_null() {
+        return None;
+    }
+
+    let mut val: i32 = 0;
+    let ok = unsafe {
+        CFNumberGetValue(
+            cf_val,
+            K_CF_NUMBER_SINT32_TYPE,
+            &mut val as *mut i32 as *mut c_void,
+        )
+    };
+    unsafe { CFRelease(cf_val) };
+
+    if ok {
+        Some(val)
+    } else {
+        None
+    }
+}
+
+fn accel_matching_dict() -> Result<CFMutableDictionaryRef, String> {
+    let class_name = CString::new("AppleSPUHIDDevice").unwrap();
+    let matching = unsafe { IOServiceMatching(class_name.as_ptr()) };
+    if matching.is_null() {
+        return Err("failed to create matching dict for AppleSPUHIDDevice".into());
+    }
+    Ok(matching)
+}
+
+/// Quick one-shot check for whether this Mac has an AppleSPUHIDDevice
+/// accelerometer at all, so `start` can hand off to another `SensorSource`
+/// on Intel hardware instead of waiting forever for a device matching
+/// notification that will never fire.
+fn probe_present() -> bool {
+    let matching = match accel_matching_dict() {
+        Ok(m) => m,
+        Err(_) => return false,
+    };
+
+    let mut iterator: IOIterator = 0;
+    let kr = unsafe {
+        IOServiceGetMatchingServices(
+            K_IO_MAIN_PORT_DEFAULT,
+            matching as CFDictionaryRef,
+            &mut iterator,
+        )
+    };
+    if kr != KERN_SUCCESS {
+        return false;
+    }
+
+    let mut found = false;
+    loop {
+        let svc = unsafe { IOIteratorNext(iterator) };
+        if svc == 0 {
+            break;
+        }
+        let usage_page = prop_int(svc, "PrimaryUsagePage");
+        let usage = prop_int(svc, "PrimaryUsage");
+        if usage_page == Some(PAGE_VENDOR) && usage == Some(USAGE_ACCEL) {
+            found = true;
+        }
+        unsafe { IOObjectRelease(svc) };
+    }
+    unsafe { IOObjectRelease(iterator) };
+
+    found
+}
+
+// ── HID report callback ────────────────────────────────────────────────────
+
+struct CallbackContext {
+    tx: mpsc::Sender<Sample>,
+    decimation_counter: u32,
+}
+
+unsafe extern "C" fn accel_report_callback(
+    context: *mut c_void,
+    _result: IOReturn,
+    _sender: *mut c_void,
+    _report_type: u32,
+    _report_id: u32,
+    report: *mut u8,
+    report_length: CFIndex,
+) {
+    let report_length = report_length as usize;
+    if report_length < IMU_REPORT_LEN {
+        return;
+    }
+
+    let ctx = &mut *(context as *mut CallbackContext);
+
+    // Decimation: keep 1 in 8 reports (~800Hz -> ~100Hz)
+    ctx.decimation_counter += 1;
+    if ctx.decimation_counter < IMU_DECIMATION {
+        return;
+    }
+    ctx.decimation_counter = 0;
+
+    let data = std::slice::from_raw_parts(report, report_length);
+    let o = IMU_DATA_OFF;
+
+    let x_raw = i32::from_le_bytes([data[o], data[o + 1], data[o + 2], data[o + 3]]);
+    let y_raw = i32::from_le_bytes([data[o + 4], data[o + 5], data[o + 6], data[o + 7]]);
+    let z_raw = i32::from_le_bytes([data[o + 8], data[o + 9], data[o + 10], data[o + 11]]);
+
+    // Only present if this report is actually long enough to carry it —
+    // the base 22-byte accelerometer report has no room for gyro words.
+    let (gx_raw, gy_raw, gz_raw) = if report_length >= GYRO_REPORT_LEN {
+        let g = GYRO_DATA_OFF;
+        (
+            i16::from_le_bytes([data[g], data[g + 1]]),
+            i16::from_le_bytes([data[g + 2], data[g + 3]]),
+            i16::from_le_bytes([data[g + 4], data[g + 5]]),
+        )
+    } else {
+        (0, 0, 0)
+    };
+
+    let sample = Sample {
+        x: x_raw as f64 / ACCEL_SCALE,
+        y: y_raw as f64 / ACCEL_SCALE,
+        z: z_raw as f64 / ACCEL_SCALE,
+        gx: gx_raw as f64 / GYRO_SCALE,
+        gy: gy_raw as f64 / GYRO_SCALE,
+        gz: gz_raw as f64 / GYRO_SCALE,
+    };
+
+    // Non-blocking send — if the receiver is full/gone, just drop the sample
+    let _ = ctx.tx.send(sample);
+}
+
+// ── Sensor initialization ───────────────────────────────────────────────────
+
+/// Wake the SPU drivers so they start producing HID reports.
+fn wake_spu_drivers() -> Result<(), String> {
+    let class_name = CString::new("AppleSPUHIDDriver").unwrap();
+    let matching = unsafe { IOServiceMatching(class_name.as_ptr()) };
+    if matching.is_null() {
+        return Err("failed to create matching dict for AppleSPUHIDDriver".into());
+    }
+
+    let mut iterator: IOIterator = 0;
+    let kr = unsafe {
+        IOServiceGetMatchingServices(
+            K_IO_MAIN_PORT_DEFAULT,
+            matching as CFDictionaryRef,
+            &mut iterator,
+        )
+    };
+    if kr != KERN_SUCCESS {
+        return Err(format!(
+            "IOServiceGetMatchingServices failed for drivers: {kr}"
+        ));
+    }
+
+    let props = [
+        ("SensorPropertyReportingState", 1),
+        ("SensorPropertyPowerState", 1),
+        ("ReportInterval", REPORT_INTERVAL_US),
+    ];
+
+    loop {
+        let svc = unsafe { IOIteratorNext(iterator) };
+        if svc == 0 {
+            break;
+        }
+        for (key, val) in &props {
+            let cf_key = cfstr(key);
+            let cf_val = cfnum32(*val);
+            unsafe {
+                IORegistryEntrySetCFProperty(svc, cf_key, cf_val as CFTypeRef);
+                CFRelease(cf_key);
+                CFRelease(cf_val as CFTypeRef);
+            }
+        }
+        unsafe { IOObjectRelease(svc) };
+    }
+
+    unsafe { IOObjectRelease(iterator) };
+    Ok(())
+}
+
+/// A live connection to the accelerometer HID device, plus everything its
+/// input-report callback needs to stay alive. Closed and freed on drop,
+/// which happens whenever the monitor sees the device go away.
+struct ActiveDevice {
+    hid_device: IOHIDDeviceRef,
+    report_buf: *mut [u8; REPORT_BUF_SZ],
+    callback_ctx: *mut CallbackContext,
+}
+
+impl Drop for ActiveDevice {
+    fn drop(&mut self) {
+        unsafe {
+            IOHIDDeviceClose(self.hid_device, 0);
+            CFRelease(self.hid_device as CFTypeRef);
+            drop(Box::from_raw(self.callback_ctx));
+            drop(Box::from_raw(self.report_buf));
+        }
+    }
+}
+
+/// Open the accelerometer HID service, register the input-report callback,
+/// and schedule it on the calling thread's run loop.
+fn attach_device(service: IOObject, tx: mpsc::Sender<Sample>) -> Result<ActiveDevice, String> {
+    let hid_device = unsafe { IOHIDDeviceCreate(K_CF_ALLOCATOR_DEFAULT, service) };
+    if hid_device.is_null() {
+        return Err("failed to create IOHIDDevice".into());
+    }
+
+    let kr = unsafe { IOHIDDeviceOpen(hid_device, 0) };
+    if kr != KERN_SUCCESS {
+        return Err(format!(
+            "failed to open IOHIDDevice (code {kr}). are you running with sudo?"
+        ));
+    }
+
+    // These are owned by the returned ActiveDevice and freed when it is
+    // dropped — they must live as long as the callback is registered.
+    let report_buf = Box::into_raw(Box::new([0u8; REPORT_BUF_SZ]));
+    let callback_ctx = Box::into_raw(Box::new(CallbackContext {
+        tx,
+        decimation_counter: 0,
+    }));
+
+    unsafe {
+        IOHIDDeviceRegisterInputReportCallback(
+            hid_device,
+            report_buf as *mut u8,
+            REPORT_BUF_SZ as CFIndex,
+            accel_report_callback,
+            callback_ctx as *mut c_void,
+        );
+        IOHIDDeviceScheduleWithRunLoop(hid_device, CFRunLoopGetCurrent(), kCFRunLoopDefaultMode);
+    }
+
+    Ok(ActiveDevice {
+        hid_device,
+        report_buf,
+        callback_ctx,
+    })
+}
+
+/// Shared state for the device-matching monitor. Lives for the process
+/// lifetime (leaked, like the run loop it's bound to) and is only ever
+/// touched from the run loop thread that dispatches IOKit notifications.
+struct MonitorContext {
+    tx: mpsc::Sender<Sample>,
+    active: Option<ActiveDevice>,
+}
+
+/// Fired when an AppleSPUHIDDevice service arrives — at startup for one
+/// already present, or after a sleep/wake cycle or SPU driver restart.
+unsafe extern "C" fn on_device_arrival(refcon: *mut c_void, iterator: IOIterator) {
+    let monitor = &mut *(refcon as *mut MonitorContext);
+
+    loop {
+        let svc = IOIteratorNext(iterator);
+        if svc == 0 {
+            break;
+        }
+
+        // "AppleSPUHIDDevice" covers more than just the accelerometer —
+        // narrow to the vendor-usage accel page, same as the old one-shot
+        // enumeration did.
+        let usage_page = prop_int(svc, "PrimaryUsagePage");
+        let usage = prop_int(svc, "PrimaryUsage");
+        if usage_page != Some(PAGE_VENDOR) || usage != Some(USAGE_ACCEL) {
+            IOObjectRelease(svc);
+            continue;
+        }
+
+        if let Err(e) = wake_spu_drivers() {
+            eprintln!("smack: failed to wake SPU drivers: {e}");
+        }
+
+        match attach_device(svc, monitor.tx.clone()) {
+            Ok(active) => {
+                eprintln!("smack: accelerometer active");
+                monitor.active = Some(active);
+            }
+            Err(e) => eprintln!("smack: failed to attach accelerometer: {e}"),
+        }
+
+        IOObjectRelease(svc);
+    }
+}
+
+/// Fired when the AppleSPUHIDDevice service terminates (sleep, unplug-ish
+/// SPU driver restart). Tears down the current device handle so the next
+/// arrival notification starts clean.
+unsafe extern "C" fn on_device_termination(refcon: *mut c_void, iterator: IOIterator) {
+    let monitor = &mut *(refcon as *mut MonitorContext);
+
+    loop {
+        let svc = IOIteratorNext(iterator);
+        if svc == 0 {
+            break;
+        }
+
+        // Ignore other AppleSPUHIDDevice services going away — only the
+        // vendor-usage accel page owns the active device handle.
+        let usage_page = prop_int(svc, "PrimaryUsagePage");
+        let usage = prop_int(svc, "PrimaryUsage");
+        if usage_page != Some(PAGE_VENDOR) || usage != Some(USAGE_ACCEL) {
+            IOObjectRelease(svc);
+            continue;
+        }
+
+        eprintln!("smack: accelerometer went away, waiting for it to come back");
+        monitor.active = None;
+        IOObjectRelease(svc);
+    }
+}
+
+// ── Public API ──────────────────────────────────────────────────────────────
+
+/// BMI286 IMU behind the AppleSPU HID interface, as found on Apple Silicon
+/// (M2+) MacBooks.
+pub struct AppleSilicon;
+
+impl SensorSource for AppleSilicon {
+    fn name(&self) -> &'static str {
+        "Apple Silicon (AppleSPU BMI286)"
+    }
+
+    /// Monitor the accelerometer. Runs the CFRunLoop on the calling thread
+    /// (blocks forever). Sends decoded samples through `tx`.
+    ///
+    /// Rather than enumerating the device once at startup, this registers
+    /// IOKit service-matching notifications so the sensor survives
+    /// sleep/wake, SPU driver restarts, and similar hot-unplug-like events:
+    /// the device is re-attached whenever it (re)appears and cleanly torn
+    /// down when it goes away, instead of leaving the daemon silently dead.
+    ///
+    /// Must be called from a dedicated thread. Returns immediately with an
+    /// error if no AppleSPU accelerometer is present, so `sensor::start`
+    /// can fall back to another backend.
+    fn start(&self, tx: mpsc::Sender<Sample>) -> Result<(), String> {
+        start(tx)
+    }
+}
+
+fn start(tx: mpsc::Sender<Sample>) -> Result<(), String> {
+    if !probe_present() {
+        return Err("no AppleSPU accelerometer found — not an Apple Silicon MacBook".into());
+    }
+
+    let notify_port = unsafe { IONotificationPortCreate(K_IO_MAIN_PORT_DEFAULT) };
+    if notify_port.is_null() {
+        return Err("failed to create IONotificationPort".into());
+    }
+
+    let run_loop = unsafe { CFRunLoopGetCurrent() };
+    let source = unsafe { IONotificationPortGetRunLoopSource(notify_port) };
+    unsafe { CFRunLoopAddSource(run_loop, source, kCFRunLoopDefaultMode) };
+
+    let monitor = Box::into_raw(Box::new(MonitorContext { tx, active: None }));
+
+    let arrival_type = CString::new(K_IO_FIRST_MATCH_NOTIFICATION).unwrap();
+    let mut arrival_iter: IOIterator = 0;
+    let kr = unsafe {
+        IOServiceAddMatchingNotification(
+            notify_port,
+            arrival_type.as_ptr(),
+            accel_matching_dict()? as CFDictionaryRef,
+            on_device_arrival,
+            monitor as *mut c_void,
+            &mut arrival_iter,
+        )
+    };
+    if kr != KERN_SUCCESS {
+        return Err(format!(
+            "IOServiceAddMatchingNotification (arrival) failed: {kr}"
+        ));
+    }
+
+    let terminated_type = CString::new(K_IO_TERMINATED_NOTIFICATION).unwrap();
+    let mut terminated_iter: IOIterator = 0;
+    let kr = unsafe {
+        IOServiceAddMatchingNotification(
+            notify_port,
+            terminated_type.as_ptr(),
+            accel_matching_dict()? as CFDictionaryRef,
+            on_device_termination,
+            monitor as *mut c_void,
+            &mut terminated_iter,
+        )
+    };
+    if kr != KERN_SUCCESS {
+        return Err(format!(
+            "IOServiceAddMatchingNotification (terminated) failed: {kr}"
+        ));
+    }
+
+    // The iterators returned above already hold every service that matches
+    // right now, and the notifications are only armed once those iterators
+    // are drained. Draining them through the callbacks here attaches an
+    // accelerometer that is already present on normal startup.
+    unsafe {
+        on_device_arrival(monitor as *mut c_void, arrival_iter);
+        on_device_termination(monitor as *mut c_void, terminated_iter);
+    }
+
+    if unsafe { (*monitor).active.is_none() } {
+        eprintln!("smack: accelerometer not present yet, waiting for it to arrive");
+    }
+
+    // Run the CFRunLoop forever (delivers HID reports and IOKit
+    // arrival/termination notifications via callback)
+    loop {
+        unsafe {
+            CFRunLoopRunInMode(kCFRunLoopDefaultMode, 1.0, false);
+        }
+    }
+}
@@ -0,0 +1,185 @@
+/// Legacy Sudden Motion Sensor reader for Intel MacBooks, selected at
+/// runtime when the AppleSPU accelerometer isn't found.
+///
+/// Polls the kernel driver directly via `IOConnectCallStructMethod` rather
+/// than registering a HID input-report callback — the SMS driver interface
+/// predates the HID-based AppleSPU path entirely.
+use std::ffi::CString;
+use std::os::raw::c_void;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use super::{Sample, SensorSource};
+
+type CFDictionaryRef = *const c_void;
+type CFMutableDictionaryRef = *mut c_void;
+
+type IOReturn = i32;
+type MachPort = u32;
+type IOIterator = u32;
+type IOObject = u32;
+type IOConnect = u32;
+
+const KERN_SUCCESS: IOReturn = 0;
+const K_IO_MAIN_PORT_DEFAULT: MachPort = 0;
+
+// Driver class names to probe, in the order real-world Intel Macs expose
+// them (SMC-mediated first, then the older I2C and PMU variants).
+const SMS_SERVICE_CLASSES: [&str; 3] = ["SMCMotionSensor", "IOI2CMotionSensor", "PMUMotionSensor"];
+
+// `IOConnectCallStructMethod` kernel function selector for "read axes" on
+// every known Sudden Motion Sensor user-client.
+const SMS_READ_FUNCTION: u32 = 5;
+
+// Raw counts -> g. The SMS reports roughly +-251 counts per g around its
+// resting 0 point, same normalization used by the long-standing third-party
+// SMS drivers (smcFanControl, sms-fakesmc, etc).
+const SMS_SCALE: f64 = 251.0;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(10); // ~100Hz
+
+#[repr(C)]
+#[derive(Default)]
+struct SmsAxes {
+    x: i16,
+    y: i16,
+    z: i16,
+}
+
+#[link(name = "IOKit", kind = "framework")]
+extern "C" {
+    fn IOServiceMatching(name: *const i8) -> CFMutableDictionaryRef;
+    fn IOServiceGetMatchingServices(
+        mainPort: MachPort,
+        matching: CFDictionaryRef,
+        existing: *mut IOIterator,
+    ) -> IOReturn;
+    fn IOIteratorNext(iterator: IOIterator) -> IOObject;
+    fn IOObjectRelease(object: IOObject) -> IOReturn;
+    fn IOServiceOpen(
+        service: IOObject,
+        owningTask: MachPort,
+        connectType: u32,
+        connect: *mut IOConnect,
+    ) -> IOReturn;
+    fn IOServiceClose(connect: IOConnect) -> IOReturn;
+    fn IOConnectCallStructMethod(
+        connect: IOConnect,
+        selector: u32,
+        inputStruct: *const c_void,
+        inputStructCnt: usize,
+        outputStruct: *mut c_void,
+        outputStructCnt: *mut usize,
+    ) -> IOReturn;
+}
+
+extern "C" {
+    fn mach_task_self() -> MachPort;
+}
+
+/// Find and open the first Sudden Motion Sensor user-client that matches
+/// one of the known service classes.
+fn open_connection() -> Result<IOConnect, String> {
+    for class in SMS_SERVICE_CLASSES {
+        let class_name = match CString::new(class) {
+            Ok(c) => c,
+            Err(_) => continue,
+        };
+        let matching = unsafe { IOServiceMatching(class_name.as_ptr()) };
+        if matching.is_null() {
+            continue;
+        }
+
+        let mut iterator: IOIterator = 0;
+        let kr = unsafe {
+            IOServiceGetMatchingServices(
+                K_IO_MAIN_PORT_DEFAULT,
+                matching as CFDictionaryRef,
+                &mut iterator,
+            )
+        };
+        if kr != KERN_SUCCESS {
+            continue;
+        }
+
+        let svc = unsafe { IOIteratorNext(iterator) };
+        unsafe { IOObjectRelease(iterator) };
+        if svc == 0 {
+            continue;
+        }
+
+        let mut connect: IOConnect = 0;
+        let kr = unsafe { IOServiceOpen(svc, mach_task_self(), 0, &mut connect) };
+        unsafe { IOObjectRelease(svc) };
+        if kr == KERN_SUCCESS {
+            return Ok(connect);
+        }
+    }
+
+    Err(
+        "no Sudden Motion Sensor found (SMCMotionSensor / IOI2CMotionSensor / PMUMotionSensor)"
+            .into(),
+    )
+}
+
+fn read_axes(connect: IOConnect) -> Result<SmsAxes, String> {
+    let mut axes = SmsAxes::default();
+    let mut out_size = std::mem::size_of::<SmsAxes>();
+
+    let kr = unsafe {
+        IOConnectCallStructMethod(
+            connect,
+            SMS_READ_FUNCTION,
+            std::ptr::null(),
+            0,
+            &mut axes as *mut SmsAxes as *mut c_void,
+            &mut out_size,
+        )
+    };
+
+    if kr != KERN_SUCCESS {
+        return Err(format!("IOConnectCallStructMethod failed: {kr}"));
+    }
+
+    Ok(axes)
+}
+
+/// Sudden Motion Sensor on pre-Apple-Silicon MacBooks, read through the
+/// legacy SMC/I2C/PMU kernel user-client rather than a HID interface.
+pub struct IntelSms;
+
+impl SensorSource for IntelSms {
+    fn name(&self) -> &'static str {
+        "Intel (Sudden Motion Sensor)"
+    }
+
+    fn start(&self, tx: mpsc::Sender<Sample>) -> Result<(), String> {
+        let connect = open_connection()?;
+        eprintln!("smack: Sudden Motion Sensor active");
+
+        loop {
+            match read_axes(connect) {
+                Ok(axes) => {
+                    let sample = Sample {
+                        x: axes.x as f64 / SMS_SCALE,
+                        y: axes.y as f64 / SMS_SCALE,
+                        z: axes.z as f64 / SMS_SCALE,
+                        // The Sudden Motion Sensor user-client only exposes
+                        // linear axes, no gyro.
+                        gx: 0.0,
+                        gy: 0.0,
+                        gz: 0.0,
+                    };
+                    // Non-blocking send — if the receiver is full/gone, just drop the sample
+                    let _ = tx.send(sample);
+                }
+                Err(e) => {
+                    unsafe { IOServiceClose(connect) };
+                    return Err(format!("Sudden Motion Sensor read failed: {e}"));
+                }
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
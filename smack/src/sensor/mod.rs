@@ -0,0 +1,52 @@
+/// Accelerometer backends. `start` probes each `SensorSource` in turn and
+/// runs whichever one claims the hardware, so the same daemon binary works
+/// on both Apple Silicon and Intel MacBooks.
+mod apple_silicon;
+mod intel_sms;
+
+use std::sync::mpsc;
+
+pub struct Sample {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    // Angular velocity in deg/s. Backends without a gyro (the Intel SMS
+    // path) report zero on all three axes.
+    pub gx: f64,
+    pub gy: f64,
+    pub gz: f64,
+}
+
+/// A source of accelerometer samples. Implementations probe whether their
+/// underlying hardware is present and, if so, take over the calling thread
+/// to pump samples into the channel until the process exits or the
+/// hardware goes away for good.
+pub trait SensorSource {
+    /// Human-readable name used in startup/error logging.
+    fn name(&self) -> &'static str;
+
+    /// Start reading samples, blocking the calling thread. Returns `Err`
+    /// immediately if this backend's hardware isn't present, so the caller
+    /// can try the next one.
+    fn start(&self, tx: mpsc::Sender<Sample>) -> Result<(), String>;
+}
+
+/// Probe each known backend in order and run whichever one claims the
+/// hardware. Must be called from a dedicated thread — the winning backend
+/// blocks forever.
+pub fn start(tx: mpsc::Sender<Sample>) -> Result<(), String> {
+    let backends: [&dyn SensorSource; 2] = [&apple_silicon::AppleSilicon, &intel_sms::IntelSms];
+
+    let mut last_err = String::from("no sensor backends available");
+    for backend in backends {
+        match backend.start(tx.clone()) {
+            Ok(()) => return Ok(()),
+            Err(e) => {
+                eprintln!("smack: {} backend unavailable: {e}", backend.name());
+                last_err = e;
+            }
+        }
+    }
+
+    Err(format!("no supported accelerometer found: {last_err}"))
+}